@@ -4,30 +4,39 @@
 //! ## Example
 //! 
 //! ```
-//! use amaryllis::Avatar;
-//! 
+//! use amaryllis::{Avatar, Shape, TextColor};
+//! use colorgrad::CustomGradient;
+//!
 //! // Simple
-//! Avatar::new(200, 200, None, None).simple([255, 255, 255, 255])
+//! Avatar::new(200, 200, None, None, None, Shape::Square, 2, None).simple(Some([255, 255, 255, 255]))
 //!     .save("simple_avatar.webp").unwrap();
-//! 
-//! // Simple with text
+//!
+//! // Simple with text, masked into a circle
 //! Avatar::new(
 //!     200, 200,
 //!     Option::Some("John Middlename Doe"),
-//!     Option::Some([0, 0, 0, 255])
-//! ).simple([255, 255, 255, 255]).save(
+//!     Option::Some(TextColor::Fixed([0, 0, 0, 255])),
+//!     None,
+//!     Shape::Circle,
+//!     2,
+//!     None
+//! ).simple(Some([255, 255, 255, 255])).save(
 //!     "simple_avatar_text.webp"
 //! ).unwrap();
-//! 
+//!
 //! // Gradient
-//! Avatar::new(200, 200, None, None).gradient(0.0025, colorgrad::reds())
+//! Avatar::new(200, 200, None, None, None, Shape::Square, 2, None).gradient(0.0025, colorgrad::reds())
 //!     .save("gradient_avatar.webp").unwrap();
 //!
-//! // Gradient with text
+//! // Gradient with text, automatically contrasted against the background
 //! Avatar::new(
 //!     200, 200,
 //!     Option::Some("John Middlename Doe"),
-//!     Option::Some([0, 0, 0, 255])
+//!     Option::Some(TextColor::Auto),
+//!     None,
+//!     Shape::RoundedRect { radius: 24 },
+//!     2,
+//!     None
 //! ).gradient(
 //!     0.0025,
 //!     CustomGradient::new().html_colors(&["deeppink", "cyan"]).build().unwrap()
@@ -37,14 +46,39 @@
 
 
 pub use colorgrad;
+use ab_glyph::{Font, FontArc, PxScale};
+#[cfg(not(feature = "shaping"))]
+use ab_glyph::ScaleFont;
 use image::{ImageBuffer, Rgba, RgbaImage};
+#[cfg(not(feature = "shaping"))]
 use imageproc::drawing::{draw_text_mut, text_size};
-use rusttype::{Font, Scale};
 use noise::NoiseFn;
 use rand::Rng;
-use crate::utils::remap;
+use crate::utils::{extract_initials, fnv1a_hash, hsl_to_rgba, luminance, remap, smoothstep};
 
 mod utils;
+#[cfg(feature = "shaping")]
+mod shaping;
+
+/// The color initials are drawn in
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TextColor {
+    /// A fixed RGBA value, drawn as-is
+    Fixed([u8; 4]),
+    /// Black or near-black/white text, chosen from the luminance of the background
+    Auto
+}
+
+/// The shape the generated image is masked into
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shape {
+    /// No masking; the image keeps its original corners
+    Square,
+    /// A circle inscribed in the image bounds
+    Circle,
+    /// A rectangle with corners rounded to `radius` pixels
+    RoundedRect { radius: u32 }
+}
 
 /// Struct representing an avatar
 #[allow(dead_code)]
@@ -54,47 +88,92 @@ pub struct Avatar {
     pub height: i32,
     /// Initials for a name, if given
     pub initials: Option<String>,
-    /// Array of four unsigned 8-bit integers, representing an RGBA value, if given
-    pub text_color: Option<[u8; 4]>
+    /// The color initials are drawn in, if given
+    pub text_color: Option<TextColor>,
+    /// FNV-1a hash of `name_string`, used to derive a stable seed/color per name
+    pub name_hash: Option<u64>,
+    /// Explicit seed overriding `name_hash`, if given
+    pub seed: Option<u64>,
+    /// The shape the generated image is masked into
+    pub shape: Shape,
+    /// Raw font file bytes, in fallback order. `fonts[0]` is the primary font; later entries
+    /// are tried in order for glyphs missing from earlier ones. Defaults to `[DejaVu Sans]`
+    pub font_data: Vec<Vec<u8>>,
+    /// `font_data` parsed once at construction time and reused for every `simple`/`gradient` call
+    fonts: Vec<FontArc>
 }
 
 #[allow(dead_code)]
 impl Avatar {
     /// Returns a new Avatar object with specificied parameters
-    /// 
+    ///
     /// # Arguments
-    /// 
+    ///
     /// * `width` - Image width as a 32-bit integer
     /// * `height` - Image height as a 32-bit integer
     /// * `name_string` - The optional name or username of the owner of the avatar. If `None`, it will be ignored
-    /// * `text_rgba` - An optional array of four unsigned 8-bit integers, representing an RGBA value. If `name_string` is `Some`, this must be `Some`
-    pub fn new(width: i32, height: i32, name_string: Option<&str>, text_rgba: Option<[u8; 4]>) -> Self {
-        return match name_string {
+    /// * `text_color` - The color to draw initials in, a `TextColor::Fixed` value or `TextColor::Auto` to contrast against the background. If `name_string` is `Some`, this must be `Some`
+    /// * `seed` - An optional seed overriding the one derived from `name_string`. Lets callers pin a specific background/noise pattern
+    /// * `shape` - The shape the generated image is masked into
+    /// * `max_initials` - How many initials to draw (clamped to `1..=3`); some locales prefer a single letter
+    /// * `fonts` - Font file bytes to draw initials with, in fallback order (`fonts[1..]` are tried for glyphs missing from `fonts[0]`). `None` or an empty `Vec` falls back to the bundled DejaVu Sans
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(width: i32, height: i32, name_string: Option<&str>, text_color: Option<TextColor>, seed: Option<u64>, shape: Shape, max_initials: u8, fonts: Option<Vec<Vec<u8>>>) -> Self {
+        let name_hash = name_string.map(|name| fnv1a_hash(name.as_bytes()));
+        let default_font = || vec![Vec::from(include_bytes!("../assets/DejaVuSans.ttf") as &[u8])];
+
+        let requested_font_data = match fonts {
+            Some(font_data) if !font_data.is_empty() => font_data,
+            _ => default_font()
+        };
+
+        // Parse bytes and the font they produce together, rather than filtering `font_data` and
+        // `fonts` independently: if one supplied font is unparseable, a pair filter drops it from
+        // both lists at once, keeping `font_data[i]`/`fonts[i]` describing the same font. Code
+        // that indexes into both by position (e.g. the shaping module) relies on that
+        let parse = |font_data: Vec<Vec<u8>>| -> Vec<(Vec<u8>, FontArc)> {
+            font_data.into_iter()
+                .filter_map(|bytes| FontArc::try_from_vec(bytes.clone()).ok().map(|font| (bytes, font)))
+                .collect()
+        };
+
+        // If every supplied font was unparseable (corrupt/truncated bytes, wrong format), this
+        // would be empty and every `self.fonts[0]` access below would panic. Fall back to the
+        // bundled default font, which is always valid, rather than carrying the garbage through
+        let mut parsed_fonts = parse(requested_font_data);
+        if parsed_fonts.is_empty() {
+            parsed_fonts = parse(default_font());
+        }
+
+        let (font_data, fonts): (Vec<Vec<u8>>, Vec<FontArc>) = parsed_fonts.into_iter().unzip();
+
+        match name_string {
             Some(name) =>  {
-                if text_rgba.is_none() {
-                    return Avatar {
+                if text_color.is_none() {
+                    Avatar {
                         width,
                         height,
                         initials: None,
-                        text_color: None
+                        text_color: None,
+                        name_hash,
+                        seed,
+                        shape,
+                        font_data,
+                        fonts
                     }
                 } else {
-                    let mut initials: String = "".to_string();
-
-                    let name_split = &name.split(" ");
-                    for word in name_split.clone() {
-                        let pos = name_split.clone().position(|x| x == word).unwrap();
-                        if pos == 0 || pos == name_split.clone().count() - 1 {
-                            initials.push(word.chars().nth(0).unwrap())
-                        }
-                    }
-
+                    let initials = extract_initials(name, max_initials);
 
                     Avatar {
                         width,
                         height,
                         initials: Option::Some(initials),
-                        text_color: text_rgba
+                        text_color,
+                        name_hash,
+                        seed,
+                        shape,
+                        font_data,
+                        fonts
                     }
                 }
             },
@@ -103,28 +182,173 @@ impl Avatar {
                     width,
                     height,
                     initials: None,
-                    text_color: None
+                    text_color: None,
+                    name_hash,
+                    seed,
+                    shape,
+                    font_data,
+                    fonts
+                }
+            }
+        }
+    }
+
+    /// Resolves the seed to use for noise generation: an explicit override, falling back to
+    /// the hash of `name_string`, falling back to a random seed when neither is available
+    fn resolve_seed(&self) -> u32 {
+        match self.seed.or(self.name_hash) {
+            Some(value) => value as u32,
+            None => rand::thread_rng().gen_range(0..4294967295)
+        }
+    }
+
+    /// Derives a stable default background color from `name_hash`/`seed`, for use when no
+    /// explicit color is supplied. Falls back to a neutral gray when neither is available
+    fn default_color(&self) -> [u8; 4] {
+        match self.seed.or(self.name_hash) {
+            Some(value) => {
+                let hue = (value % 360) as f64;
+                hsl_to_rgba(hue, 0.45, 0.55)
+            },
+            None => [128, 128, 128, 255]
+        }
+    }
+
+    /// Resolves `self.text_color` against a background luminance (`0.0..=255.0`), picking
+    /// near-black or near-white for `TextColor::Auto`
+    fn resolve_text_color(&self, background_luminance: f64) -> Option<[u8; 4]> {
+        self.text_color.map(|text_color| match text_color {
+            TextColor::Fixed(rgba) => rgba,
+            TextColor::Auto => {
+                if background_luminance > 128.0 {
+                    [20, 20, 20, 255]
+                } else {
+                    [235, 235, 235, 255]
+                }
+            }
+        })
+    }
+
+    /// Masks `image_buf` into `self.shape` by writing its alpha channel: pixels fully inside
+    /// the shape become opaque, pixels fully outside become transparent, and a 1.5px band at
+    /// the boundary is smoothstep-interpolated for anti-aliased edges
+    fn apply_shape_mask(&self, image_buf: &mut RgbaImage) {
+        const BAND: f64 = 1.5;
+
+        match self.shape {
+            Shape::Square => (),
+            Shape::Circle => {
+                let cx = self.width as f64 / 2.0;
+                let cy = self.height as f64 / 2.0;
+                let radius = self.width.min(self.height) as f64 / 2.0;
+
+                for (x, y, pixel) in image_buf.enumerate_pixels_mut() {
+                    let dx = x as f64 + 0.5 - cx;
+                    let dy = y as f64 + 0.5 - cy;
+                    let distance = radius - dx.hypot(dy);
+                    let alpha = (smoothstep(-BAND, BAND, distance) * 255.0).round() as u16;
+                    pixel[3] = ((pixel[3] as u16 * alpha) / 255) as u8;
+                }
+            },
+            Shape::RoundedRect { radius } => {
+                let hw = self.width as f64 / 2.0;
+                let hh = self.height as f64 / 2.0;
+                let radius = (radius as f64).min(hw).min(hh);
+
+                for (x, y, pixel) in image_buf.enumerate_pixels_mut() {
+                    let px = (x as f64 + 0.5 - hw).abs() - (hw - radius);
+                    let py = (y as f64 + 0.5 - hh).abs() - (hh - radius);
+                    let outside = px.max(0.0).hypot(py.max(0.0)) + px.max(py).min(0.0) - radius;
+                    let alpha = (smoothstep(-BAND, BAND, -outside) * 255.0).round() as u16;
+                    pixel[3] = ((pixel[3] as u16 * alpha) / 255) as u8;
                 }
             }
+        }
+    }
+
+    /// Returns the first font in `self.fonts` that has a real glyph for `c`, falling back to
+    /// the primary font (`self.fonts[0]`) if none of them do
+    fn font_for_char(&self, c: char) -> &FontArc {
+        self.fonts.iter()
+            .find(|font| font.glyph_id(c).0 != 0)
+            .unwrap_or(&self.fonts[0])
+    }
+
+    /// Draws `self.initials` centered onto `image_buf` in `text_color`, if initials are set.
+    /// With the `shaping` feature enabled, text is run through rustybuzz first so complex
+    /// scripts and RTL names shape and position correctly, picking whichever font in
+    /// `self.fonts` fully covers the string (see `shaping::font_index_for_text`); otherwise
+    /// each character is laid out individually with ab_glyph, falling back through
+    /// `self.fonts` per character for glyphs missing from the primary font
+    fn draw_initials(&self, image_buf: &mut RgbaImage, text_color: [u8; 4]) {
+        let initials = match &self.initials {
+            Some(initials) => initials,
+            None => return
+        };
+
+        let font_scale = PxScale {
+            x: self.width as f32 / 2.0,
+            y: self.height as f32 / 2.0
         };
+
+        #[cfg(feature = "shaping")]
+        {
+            shaping::draw_shaped_text(
+                image_buf,
+                &self.font_data,
+                &self.fonts,
+                font_scale,
+                text_color,
+                initials,
+                self.width / 2,
+                self.height / 2
+            );
+        }
+
+        #[cfg(not(feature = "shaping"))]
+        {
+            let (_, text_height) = text_size(font_scale, &self.fonts[0], initials);
+            let text_height = text_height as i32;
+
+            let advance = |c: char| {
+                let font = self.font_for_char(c);
+                font.as_scaled(font_scale).h_advance(font.glyph_id(c))
+            };
+            let total_width: f32 = initials.chars().map(advance).sum();
+
+            // Center on real ascent/descent rather than a fixed fudge factor
+            let primary = self.fonts[0].as_scaled(font_scale);
+            let vertical_offset = ((primary.ascent() + primary.descent()) / 2.0).round() as i32;
+
+            let mut pen_x = self.width as f32 / 2.0 - total_width / 2.0;
+            let pen_y = self.height / 2 - text_height / 2 - vertical_offset;
+
+            for c in initials.chars() {
+                let font = self.font_for_char(c);
+                draw_text_mut(image_buf, Rgba(text_color), pen_x.round() as i32, pen_y, font_scale, font, &c.to_string());
+                pen_x += advance(c);
+            }
+        }
     }
 
     /// Returns an RGBA Image (`RgbaImage`) of a solid color (and initials of the user, if given)
-    /// 
+    ///
     /// # Arguments
-    /// 
-    /// * `color_rgba` - An array of four unsigned 8-bit integers, representing an RGBA value.
-    /// 
+    ///
+    /// * `color_rgba` - An array of four unsigned 8-bit integers, representing an RGBA value. If `None`, a color derived from the avatar's name/seed is used
+    ///
     /// # Example
     /// ```
-    /// use amaryllis::Avatar;
-    /// 
-    /// let avatar = Avatar::new(200, 200, None, None).simple([255, 255, 255, 255]);
-    /// 
+    /// use amaryllis::{Avatar, Shape};
+    ///
+    /// let avatar = Avatar::new(200, 200, None, None, None, Shape::Circle, 2, None).simple(Some([255, 255, 255, 255]));
+    ///
     /// avatar.save("cool_avatar.png");
     /// ```
     #[allow(dead_code)]
-    pub fn simple(&self, color_rgba: [u8; 4]) -> RgbaImage {
+    pub fn simple(&self, color_rgba: Option<[u8; 4]>) -> RgbaImage {
+        let color_rgba = color_rgba.unwrap_or_else(|| self.default_color());
+
         let mut image_buf: RgbaImage = ImageBuffer::new(
             u32::try_from(self.width).unwrap(),
             u32::try_from(self.height).unwrap()
@@ -134,35 +358,12 @@ impl Avatar {
             *pixel = Rgba(color_rgba);
         }
 
-        match &self.initials {
-            Some(initials) => {
-                match self.text_color {
-                    Some(text_color) => {
-                        let font = Vec::from(include_bytes!("../assets/Roboto.ttf") as &[u8]);
-                        let font = Font::try_from_vec(font).unwrap();
-
-                        let font_scale = Scale {
-                            x: self.width as f32 / 2.0,
-                            y: self.height as f32 / 2.0
-                        };
-
-                        let (text_width, text_height) = text_size(font_scale, &font, &initials);
-
-                        draw_text_mut(
-                            &mut image_buf,
-                            Rgba(text_color),
-                            self.width / 2 - text_width / 2,
-                            self.height / 2 - text_height / 2 - 6,
-                            font_scale, &font, 
-                            &initials
-                        )
-                    },
-                    None => ()
-                }
-            },
-            None => ()
+        if let Some(text_color) = self.resolve_text_color(luminance(color_rgba)) {
+            self.draw_initials(&mut image_buf, text_color);
         }
 
+        self.apply_shape_mask(&mut image_buf);
+
         image_buf
     }
 
@@ -173,27 +374,37 @@ impl Avatar {
     /// 
     /// * `noise_scale` - A 64-bit float value representing noise scale. Higher values are more noisy
     /// * `gradient` - A `Gradient` value to use as a background
-    /// 
+    ///
+    /// Noise is seeded from the avatar's `seed` (or the hash of its `name_string`, if no
+    /// explicit seed was given), so the same name reproduces the same background every time.
+    /// When neither is available, a random seed is used as before. With `TextColor::Auto`,
+    /// the text color is chosen from the average luminance of a coarse 16x16 grid sampled
+    /// over the gradient, since the background itself isn't a single solid color.
+    ///
     /// # Example
     /// ```
-    /// use amaryllis::Avatar;
+    /// use amaryllis::{Avatar, Shape, TextColor};
     /// use colorgrad::CustomGradient;
-    /// 
+    ///
     /// let avatar = Avatar::new(
-    ///     200, 
-    ///     200, 
+    ///     200,
+    ///     200,
     ///     Option::Some("John Middlename Doe"),
-    ///     Option::Some([0, 0, 0, 255])
+    ///     Option::Some(TextColor::Auto),
+    ///     None,
+    ///     Shape::RoundedRect { radius: 24 },
+    ///     2,
+    ///     None
     /// ).gradient(
     ///     0.0025,
     ///     CustomGradient::new().html_colors(&["deeppink", "cyan"]).build().unwrap()
     /// );
-    /// 
+    ///
     /// avatar.save("cool_avatar.png");
     /// ```
     #[allow(dead_code)]
     pub fn gradient(&self, noise_scale: f64, gradient: colorgrad::Gradient) -> RgbaImage {
-        let noise = noise::OpenSimplex::new(rand::thread_rng().gen_range(0..4294967295));
+        let noise = noise::OpenSimplex::new(self.resolve_seed());
 
         let mut image_buf: RgbaImage = ImageBuffer::new(
             u32::try_from(self.width).unwrap(),
@@ -206,35 +417,27 @@ impl Avatar {
             *pixel = Rgba(rgba);
         }
 
-        match &self.initials {
-            Some(initials) => {
-                match self.text_color {
-                    Some(text_color) => {
-                        let font = Vec::from(include_bytes!("../assets/Roboto.ttf") as &[u8]);
-                        let font = Font::try_from_vec(font).unwrap();
-
-                        let font_scale = Scale {
-                            x: self.width as f32 / 2.0,
-                            y: self.height as f32 / 2.0
-                        };
-
-                        let (text_width, text_height) = text_size(font_scale, &font, &initials);
-
-                        draw_text_mut(
-                            &mut image_buf,
-                            Rgba(text_color),
-                            self.width / 2 - text_width / 2,
-                            self.height / 2 - text_height / 2 - 6,
-                            font_scale, &font, 
-                            &initials
-                        )
-                    },
-                    None => ()
+        if self.initials.is_some() {
+            const GRID: i32 = 16;
+            let mut luminance_sum = 0.0;
+            for gy in 0..GRID {
+                for gx in 0..GRID {
+                    let x = gx * (self.width - 1).max(0) / (GRID - 1).max(1);
+                    let y = gy * (self.height - 1).max(0) / (GRID - 1).max(1);
+                    let noise_t = noise.get([x as f64 * noise_scale, y as f64 * noise_scale]);
+                    let rgba = gradient.at(remap(noise_t, -1.0, 1.0, 0.0, 1.0)).to_rgba8();
+                    luminance_sum += luminance(rgba);
                 }
-            },
-            None => ()
+            }
+            let average_luminance = luminance_sum / (GRID * GRID) as f64;
+
+            if let Some(text_color) = self.resolve_text_color(average_luminance) {
+                self.draw_initials(&mut image_buf, text_color);
+            }
         }
 
+        self.apply_shape_mask(&mut image_buf);
+
         image_buf
     }
 
@@ -244,11 +447,12 @@ impl Avatar {
 
 #[cfg(test)]
 mod tests {
-    use crate::Avatar;
+    use crate::{Avatar, Shape, TextColor};
 
     use std::fs;
     use chrono::{DateTime, Utc};
     use colorgrad::CustomGradient;
+    use image::{ImageBuffer, Rgba, RgbaImage};
 
     #[test]
     fn main() {
@@ -266,9 +470,13 @@ mod tests {
             200,
             200,
             None,
+            None,
+            None,
+            Shape::Square,
+            2,
             None
         ).simple(
-            [255, 255, 255, 255],
+            Some([255, 255, 255, 255]),
         ).save(format!("test_results/simple/{timestamp}.webp")).unwrap();
 
 
@@ -277,17 +485,39 @@ mod tests {
             200,
             200,
             Option::Some("John Middlename Doe"),
-            Option::Some([0, 0, 0, 255])
+            Option::Some(TextColor::Fixed([0, 0, 0, 255])),
+            None,
+            Shape::Square,
+            2,
+            None
         ).simple(
-            [255, 255, 255, 255],
+            Some([255, 255, 255, 255]),
         ).save(format!("test_results/simple/text/{timestamp}.webp")).unwrap();
-        
+
+
+        // Simple with a name-derived default color, auto-contrasted text, a circle mask, and a
+        // single initial (as some locales prefer)
+        Avatar::new(
+            200,
+            200,
+            Option::Some("Jane Doe"),
+            Option::Some(TextColor::Auto),
+            None,
+            Shape::Circle,
+            1,
+            None
+        ).simple(None).save(format!("test_results/simple/text/{timestamp}_named.webp")).unwrap();
+
 
         // Gradient
         Avatar::new(
             200,
             200,
             None,
+            None,
+            None,
+            Shape::Square,
+            2,
             None
         ).gradient(
             0.0025,
@@ -295,18 +525,117 @@ mod tests {
         )
         .save(format!("test_results/gradient/{timestamp}.webp")).unwrap();
 
-        
-        // Gradient with text
+
+        // Gradient with text, masked into a rounded rectangle
         Avatar::new(
             200,
             200,
             Option::Some("John Middlename Doe"),
-            Option::Some([0, 0, 0, 255])
+            Option::Some(TextColor::Auto),
+            None,
+            Shape::RoundedRect { radius: 24 },
+            2,
+            None
         ).gradient(
             0.0025,
             CustomGradient::new().html_colors(&["deeppink", "cyan"]).build().unwrap()
         )
         .save(format!("test_results/gradient/text/{timestamp}.webp")).unwrap();
     }
+
+    #[test]
+    fn same_name_produces_the_same_hash_and_default_color() {
+        let a = Avatar::new(200, 200, Some("Jane Doe"), None, None, Shape::Square, 2, None);
+        let b = Avatar::new(200, 200, Some("Jane Doe"), None, None, Shape::Square, 2, None);
+        assert_eq!(a.name_hash, b.name_hash);
+        assert_eq!(a.default_color(), b.default_color());
+
+        let c = Avatar::new(200, 200, Some("John Doe"), None, None, Shape::Square, 2, None);
+        assert_ne!(a.name_hash, c.name_hash);
+    }
+
+    #[test]
+    fn explicit_seed_overrides_the_name_hash() {
+        let named = Avatar::new(200, 200, Some("Jane Doe"), None, None, Shape::Square, 2, None);
+        let seeded = Avatar::new(200, 200, Some("Jane Doe"), None, Some(42), Shape::Square, 2, None);
+        assert_ne!(named.default_color(), seeded.default_color());
+
+        let other_seeded = Avatar::new(200, 200, Some("John Doe"), None, Some(42), Shape::Square, 2, None);
+        assert_eq!(seeded.default_color(), other_seeded.default_color());
+    }
+
+    #[test]
+    fn unnamed_avatar_without_a_seed_falls_back_to_neutral_gray() {
+        let avatar = Avatar::new(200, 200, None, None, None, Shape::Square, 2, None);
+        assert_eq!(avatar.default_color(), [128, 128, 128, 255]);
+    }
+
+    #[test]
+    fn fixed_text_color_passes_through_regardless_of_background() {
+        let avatar = Avatar::new(
+            200, 200, Some("Jane Doe"), Some(TextColor::Fixed([1, 2, 3, 4])), None, Shape::Square, 2, None
+        );
+        assert_eq!(avatar.resolve_text_color(0.0), Some([1, 2, 3, 4]));
+        assert_eq!(avatar.resolve_text_color(255.0), Some([1, 2, 3, 4]));
+    }
+
+    #[test]
+    fn auto_text_color_picks_near_white_on_dark_backgrounds() {
+        let avatar = Avatar::new(200, 200, Some("Jane Doe"), Some(TextColor::Auto), None, Shape::Square, 2, None);
+        assert_eq!(avatar.resolve_text_color(0.0), Some([235, 235, 235, 255]));
+        assert_eq!(avatar.resolve_text_color(128.0), Some([235, 235, 235, 255]));
+    }
+
+    #[test]
+    fn auto_text_color_picks_near_black_on_light_backgrounds() {
+        let avatar = Avatar::new(200, 200, Some("Jane Doe"), Some(TextColor::Auto), None, Shape::Square, 2, None);
+        assert_eq!(avatar.resolve_text_color(255.0), Some([20, 20, 20, 255]));
+    }
+
+    #[test]
+    fn no_text_color_when_no_name_was_given() {
+        let avatar = Avatar::new(200, 200, None, None, None, Shape::Square, 2, None);
+        assert_eq!(avatar.resolve_text_color(0.0), None);
+    }
+
+    fn opaque_buffer(width: i32, height: i32) -> RgbaImage {
+        let mut image_buf: RgbaImage = ImageBuffer::new(width as u32, height as u32);
+        for (_, _, pixel) in image_buf.enumerate_pixels_mut() {
+            *pixel = Rgba([255, 255, 255, 255]);
+        }
+        image_buf
+    }
+
+    #[test]
+    fn square_mask_leaves_every_pixel_fully_opaque() {
+        let avatar = Avatar::new(200, 200, None, None, None, Shape::Square, 2, None);
+        let mut image_buf = opaque_buffer(200, 200);
+        avatar.apply_shape_mask(&mut image_buf);
+
+        assert_eq!(image_buf.get_pixel(0, 0)[3], 255);
+        assert_eq!(image_buf.get_pixel(100, 100)[3], 255);
+    }
+
+    #[test]
+    fn circle_mask_zeroes_the_corners_and_keeps_the_center_opaque() {
+        let avatar = Avatar::new(200, 200, None, None, None, Shape::Circle, 2, None);
+        let mut image_buf = opaque_buffer(200, 200);
+        avatar.apply_shape_mask(&mut image_buf);
+
+        assert_eq!(image_buf.get_pixel(0, 0)[3], 0);
+        assert_eq!(image_buf.get_pixel(199, 199)[3], 0);
+        assert_eq!(image_buf.get_pixel(100, 100)[3], 255);
+    }
+
+    #[test]
+    fn rounded_rect_mask_zeroes_the_corners_and_keeps_the_center_opaque() {
+        let avatar = Avatar::new(200, 200, None, None, None, Shape::RoundedRect { radius: 24 }, 2, None);
+        let mut image_buf = opaque_buffer(200, 200);
+        avatar.apply_shape_mask(&mut image_buf);
+
+        assert_eq!(image_buf.get_pixel(0, 0)[3], 0);
+        assert_eq!(image_buf.get_pixel(199, 199)[3], 0);
+        assert_eq!(image_buf.get_pixel(100, 100)[3], 255);
+    }
 }
 
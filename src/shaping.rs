@@ -0,0 +1,105 @@
+//! Complex-script and RTL text shaping, built on `rustybuzz`. The default rendering path lays
+//! glyphs out left-to-right using raw font metrics, which renders Arabic unjoined and
+//! mis-positions CJK/Indic clusters. This module shapes the text first and blits each glyph at
+//! the position and advance rustybuzz reports. Visual (left-to-right draw order) reordering for
+//! right-to-left runs is handled by rustybuzz itself once `Direction::RightToLeft` is set - the
+//! shaped glyph buffer is always in left-to-right pen order regardless of script direction, so
+//! this module never needs to reverse anything itself.
+
+use ab_glyph::{Font, FontArc, PxScale, ScaleFont};
+use image::RgbaImage;
+use rustybuzz::{Direction, Face, UnicodeBuffer};
+
+/// Codepoint ranges of scripts that are conventionally written right-to-left
+fn is_rtl(text: &str) -> bool {
+    text.chars().any(|c| matches!(
+        c as u32,
+        0x0590..=0x08FF | 0xFB1D..=0xFDFF | 0xFE70..=0xFEFF
+    ))
+}
+
+/// Picks the first font in `fonts` that has a real glyph for every character in `text`, falling
+/// back to the primary font (`fonts[0]`) if none of them fully cover it. Shaping needs a single
+/// font per run (rustybuzz's glyph IDs are only meaningful against the face that produced them),
+/// so unlike `Avatar::font_for_char` this chooses one font for the whole string rather than
+/// falling back per-character
+fn font_index_for_text(fonts: &[FontArc], text: &str) -> usize {
+    fonts.iter()
+        .position(|font| text.chars().all(|c| font.glyph_id(c).0 != 0))
+        .unwrap_or(0)
+}
+
+/// Shapes `text` with rustybuzz and draws each glyph onto `image_buf` in `text_color`, centered
+/// on `(center_x, center_y)`. Silently does nothing if no font bytes can be parsed by rustybuzz.
+/// Characters with no glyph in the chosen font are dropped without comment, same as an
+/// unsupported character would be with the non-shaping rendering path
+#[allow(clippy::too_many_arguments)]
+pub fn draw_shaped_text(
+    image_buf: &mut RgbaImage,
+    font_data: &[Vec<u8>],
+    fonts: &[FontArc],
+    font_scale: PxScale,
+    text_color: [u8; 4],
+    text: &str,
+    center_x: i32,
+    center_y: i32
+) {
+    let font_idx = font_index_for_text(fonts, text);
+    let font = &fonts[font_idx];
+
+    let face = match Face::from_slice(&font_data[font_idx], 0) {
+        Some(face) => face,
+        None => return
+    };
+
+    let mut buffer = UnicodeBuffer::new();
+    buffer.push_str(text);
+    buffer.guess_segment_properties();
+
+    if is_rtl(text) {
+        buffer.set_direction(Direction::RightToLeft);
+    }
+
+    let glyph_buffer = rustybuzz::shape(&face, &[], buffer);
+    let units_per_em = face.units_per_em() as f32;
+    let scale = font_scale.y / units_per_em;
+
+    let infos = glyph_buffer.glyph_infos();
+    let positions = glyph_buffer.glyph_positions();
+
+    let total_advance: f32 = positions.iter().map(|position| position.x_advance as f32 * scale).sum();
+
+    // Center on real ascent/descent rather than the glyph baseline, matching the non-shaping
+    // path in lib.rs
+    let scaled_font = font.as_scaled(font_scale);
+    let vertical_offset = ((scaled_font.ascent() + scaled_font.descent()) / 2.0).round();
+
+    let mut pen_x = center_x as f32 - total_advance / 2.0;
+    let pen_y = center_y as f32 + vertical_offset;
+
+    for (info, position) in infos.iter().zip(positions.iter()) {
+        let glyph_id = ab_glyph::GlyphId(info.glyph_id as u16).with_scale_and_position(
+            font_scale,
+            ab_glyph::point(
+                pen_x + position.x_offset as f32 * scale,
+                pen_y - position.y_offset as f32 * scale
+            )
+        );
+
+        if let Some(outline) = font.outline_glyph(glyph_id) {
+            let bounds = outline.px_bounds();
+            outline.draw(|x, y, coverage| {
+                let px = bounds.min.x as i32 + x as i32;
+                let py = bounds.min.y as i32 + y as i32;
+
+                if px >= 0 && py >= 0 && (px as u32) < image_buf.width() && (py as u32) < image_buf.height() {
+                    let pixel = image_buf.get_pixel_mut(px as u32, py as u32);
+                    let alpha = (coverage * text_color[3] as f32) as u8;
+                    *pixel = image::Rgba([text_color[0], text_color[1], text_color[2], alpha.max(pixel[3])]);
+                }
+            });
+        }
+
+        pen_x += position.x_advance as f32 * scale;
+    }
+}
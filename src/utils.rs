@@ -1,4 +1,129 @@
+use unicode_segmentation::UnicodeSegmentation;
+
 // taken from https://docs.rs/colorgrad/latest/colorgrad/#colored-noise
 pub fn remap(t: f64, a: f64, b: f64, c: f64, d: f64) -> f64 {
     (t - a) * ((d - c) / (b - a)) + c
 }
+
+/// Extracts up to `max_initials` (clamped to `1..=3`) initials from `name`, one grapheme
+/// cluster per chosen word. Splits on Unicode whitespace and ignores empty tokens, so leading/
+/// trailing/doubled whitespace can't panic. Returns an empty string if `name` has no usable
+/// words. When there are more words than `max_initials`, the leading words are preferred but
+/// the last word is always kept, matching the familiar "first + last initial" pattern
+pub fn extract_initials(name: &str, max_initials: u8) -> String {
+    let words: Vec<&str> = name.split_whitespace().collect();
+    if words.is_empty() {
+        return String::new();
+    }
+
+    let max_initials = max_initials.clamp(1, 3) as usize;
+
+    let mut indices: Vec<usize> = Vec::new();
+    if words.len() <= max_initials {
+        indices.extend(0..words.len());
+    } else {
+        indices.extend(0..max_initials - 1);
+        indices.push(words.len() - 1);
+    }
+
+    indices.into_iter()
+        .filter_map(|i| words[i].graphemes(true).next())
+        .collect()
+}
+
+/// Hashes a byte slice with FNV-1a, used to derive a stable seed/color from a name
+pub fn fnv1a_hash(bytes: &[u8]) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+
+    let mut hash = FNV_OFFSET_BASIS;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+/// Hermite-interpolates `x` between `edge0` and `edge1`, clamped to `0.0..=1.0`
+pub fn smoothstep(edge0: f64, edge1: f64, x: f64) -> f64 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Computes the relative luminance (`0.0..=255.0`) of an RGBA color, ignoring alpha
+pub fn luminance(rgba: [u8; 4]) -> f64 {
+    let [r, g, b, _] = rgba;
+    (299.0 * r as f64 + 587.0 * g as f64 + 114.0 * b as f64) / 1000.0
+}
+
+/// Converts an HSL color (hue in degrees, saturation/lightness in `0.0..=1.0`) to an RGBA value
+pub fn hsl_to_rgba(h: f64, s: f64, l: f64) -> [u8; 4] {
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let h_prime = h / 60.0;
+    let x = c * (1.0 - (h_prime % 2.0 - 1.0).abs());
+    let m = l - c / 2.0;
+
+    let (r1, g1, b1) = if (0.0..1.0).contains(&h_prime) {
+        (c, x, 0.0)
+    } else if (1.0..2.0).contains(&h_prime) {
+        (x, c, 0.0)
+    } else if (2.0..3.0).contains(&h_prime) {
+        (0.0, c, x)
+    } else if (3.0..4.0).contains(&h_prime) {
+        (0.0, x, c)
+    } else if (4.0..5.0).contains(&h_prime) {
+        (x, 0.0, c)
+    } else {
+        (c, 0.0, x)
+    };
+
+    [
+        (((r1 + m) * 255.0).round()) as u8,
+        (((g1 + m) * 255.0).round()) as u8,
+        (((b1 + m) * 255.0).round()) as u8,
+        255
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extract_initials_empty_and_whitespace_only_names_dont_panic() {
+        assert_eq!(extract_initials("", 2), "");
+        assert_eq!(extract_initials("   ", 2), "");
+    }
+
+    #[test]
+    fn extract_initials_leading_trailing_and_doubled_whitespace() {
+        assert_eq!(extract_initials("  John   Doe  ", 2), "JD");
+    }
+
+    #[test]
+    fn extract_initials_takes_first_plus_last_when_over_the_limit() {
+        assert_eq!(extract_initials("John Middlename Doe", 2), "JD");
+    }
+
+    #[test]
+    fn extract_initials_clamps_max_initials_to_1_3() {
+        // max_initials 0 clamps to 1, which (being fewer than the word count) keeps only the
+        // last word, matching the "last initial" half of the first+last convention
+        assert_eq!(extract_initials("John Middlename Doe", 0), "D");
+        assert_eq!(extract_initials("John Middlename Doe", 5), "JMD");
+    }
+
+    #[test]
+    fn extract_initials_single_word() {
+        assert_eq!(extract_initials("Cher", 2), "C");
+    }
+
+    #[test]
+    fn extract_initials_uses_grapheme_clusters_not_codepoints() {
+        // A regional-indicator flag and a combining-accent "word" are each one grapheme
+        // cluster but multiple codepoints/chars - naive `chars().nth(0)` would still work
+        // here by luck, but slicing by codepoint elsewhere in the pipeline would not
+        assert_eq!(extract_initials("🇯🇵 太郎", 2), "🇯🇵太");
+        assert_eq!(extract_initials("e\u{0301}cole Polytechnique", 2), "e\u{0301}P");
+    }
+}